@@ -1,6 +1,9 @@
+use rand::seq::SliceRandom;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 
 use std::cmp::Ordering;
+use std::ops::Range;
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Suit {
@@ -166,6 +169,98 @@ impl Side {
     }
 }
 
+/// A card rank, ace-high and stored as its numeric value (2 through 14,
+/// with 10..=14 being T, J, Q, K, A).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Rank(u8);
+
+/// A rank string was neither a single `2`-`9` digit nor one of `T`/`J`/`Q`/`K`/`A`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ParseRankError;
+
+impl std::fmt::Display for ParseRankError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid rank")
+    }
+}
+
+impl std::error::Error for ParseRankError {}
+
+impl Rank {
+    /// Builds a `Rank` from its numeric value, rejecting anything outside 2..=14.
+    pub fn new(value: u8) -> Option<Rank> {
+        if (2..=14).contains(&value) {
+            Some(Rank(value))
+        } else {
+            None
+        }
+    }
+
+    pub fn value(self) -> u8 {
+        self.0
+    }
+
+    pub fn is_honor(self) -> bool {
+        self.0 >= 10
+    }
+
+    pub fn is_ace(self) -> bool {
+        self.0 == 14
+    }
+
+    pub fn is_king(self) -> bool {
+        self.0 == 13
+    }
+
+    /// Whether `other` is the next rank down from `self`, e.g. `K.is_followed_by(Q)`.
+    pub fn is_followed_by(self, other: Rank) -> bool {
+        self.0 == other.0 + 1
+    }
+
+    pub fn label(self) -> char {
+        match self.0 {
+            2..=9 => (b'0' + self.0) as char,
+            10 => 'T',
+            11 => 'J',
+            12 => 'Q',
+            13 => 'K',
+            14 => 'A',
+            _ => unreachable!(),
+        }
+    }
+
+    fn from_char(c: char) -> Result<Rank, ParseRankError> {
+        match c {
+            '2'..='9' => Ok(Rank(c as u8 - b'0')),
+            'T' | 't' => Ok(Rank(10)),
+            'J' | 'j' => Ok(Rank(11)),
+            'Q' | 'q' => Ok(Rank(12)),
+            'K' | 'k' => Ok(Rank(13)),
+            'A' | 'a' => Ok(Rank(14)),
+            _ => Err(ParseRankError),
+        }
+    }
+}
+
+impl std::fmt::Display for Rank {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.label())
+    }
+}
+
+impl std::str::FromStr for Rank {
+    type Err = ParseRankError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        let c = chars.next().ok_or(ParseRankError)?;
+        if chars.next().is_some() {
+            return Err(ParseRankError);
+        }
+        Rank::from_char(c)
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Holding(pub u32);
 
@@ -174,25 +269,25 @@ impl Holding {
         Holding(0)
     }
 
-    pub fn add(&mut self, rank: u32) {
-        self.0 |= 1 << rank;
+    pub fn add(&mut self, rank: Rank) {
+        self.0 |= 1 << rank.value();
     }
 
-    pub fn remove(&mut self, rank: u32) {
-        self.0 &= !(1 << rank);
+    pub fn remove(&mut self, rank: Rank) {
+        self.0 &= !(1 << rank.value());
     }
 
-    pub fn contains(&self, rank: u32) -> bool {
-        self.0 & (1 << rank) != 0
+    pub fn contains(&self, rank: Rank) -> bool {
+        self.0 & (1 << rank.value()) != 0
     }
 
     pub fn iter(self) -> HoldingIterator {
         let mut front = 2;
-        while front < 15 && !self.contains(front) {
+        while front < 15 && !self.contains(Rank(front)) {
             front += 1;
         }
         let mut back = 14;
-        while back > 1 && !self.contains(back) {
+        while back > 1 && !self.contains(Rank(back)) {
             back -= 1;
         }
         HoldingIterator {
@@ -205,10 +300,35 @@ impl Holding {
     pub fn count(self) -> u32 {
         self.0.count_ones()
     }
+
+    pub fn parse(s: &str) -> Result<Self, ParseRankError> {
+        s.parse()
+    }
+}
+
+impl std::fmt::Display for Holding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for rank in self.iter().rev() {
+            write!(f, "{}", rank)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for Holding {
+    type Err = ParseRankError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut holding = Holding::new();
+        for c in s.chars() {
+            holding.add(Rank::from_char(c)?);
+        }
+        Ok(holding)
+    }
 }
 
 impl std::iter::IntoIterator for Holding {
-    type Item = u32;
+    type Item = Rank;
     type IntoIter = HoldingIterator;
 
     fn into_iter(self) -> Self::IntoIter {
@@ -216,12 +336,12 @@ impl std::iter::IntoIterator for Holding {
     }
 }
 
-impl std::iter::FromIterator<u32> for Holding {
-    fn from_iter<I: IntoIterator<Item = u32>>(iter: I) -> Self {
+impl std::iter::FromIterator<Rank> for Holding {
+    fn from_iter<I: IntoIterator<Item = Rank>>(iter: I) -> Self {
         let mut holding = Holding::new();
 
-        for i in iter {
-            holding.add(i);
+        for rank in iter {
+            holding.add(rank);
         }
 
         holding
@@ -231,20 +351,20 @@ impl std::iter::FromIterator<u32> for Holding {
 #[derive(Copy, Clone, Debug)]
 pub struct HoldingIterator {
     holding: Holding,
-    front: u32,
-    back: u32,
+    front: u8,
+    back: u8,
 }
 
 impl std::iter::Iterator for HoldingIterator {
-    type Item = u32;
+    type Item = Rank;
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.front > self.back {
             None
         } else {
-            let ret = Some(self.front);
+            let ret = Some(Rank(self.front));
             self.front += 1;
-            while self.front < 15 && !self.holding.contains(self.front) {
+            while self.front < 15 && !self.holding.contains(Rank(self.front)) {
                 self.front += 1;
             }
             ret
@@ -257,9 +377,9 @@ impl std::iter::DoubleEndedIterator for HoldingIterator {
         if self.front > self.back {
             None
         } else {
-            let ret = Some(self.back);
+            let ret = Some(Rank(self.back));
             self.back -= 1;
-            while self.back > 1 && !self.holding.contains(self.back) {
+            while self.back > 1 && !self.holding.contains(Rank(self.back)) {
                 self.back -= 1;
             }
             ret
@@ -593,6 +713,33 @@ impl std::fmt::Display for Contract {
     }
 }
 
+impl Contract {
+    /// An undoubled contract at the given `level`/`strain`, as named by a bid.
+    pub fn from_bid(level: u8, strain: Strain) -> Contract {
+        Contract {
+            level,
+            strain,
+            doubling: Doubling::Undoubled,
+        }
+    }
+}
+
+/// Contracts are ordered by level, then by strain, same as the bidding ladder.
+/// Doubling is ignored, since a double never changes what's legal to bid over it.
+impl Ord for Contract {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.level
+            .cmp(&other.level)
+            .then_with(|| self.strain.cmp(&other.strain))
+    }
+}
+
+impl PartialOrd for Contract {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
 pub enum Doubling {
     Undoubled,
@@ -608,17 +755,1013 @@ pub enum Call {
     Redouble,
 }
 
+impl Call {
+    /// Whether this call would name a contract that outranks `current`, the
+    /// highest contract named so far (if any). Only `Bid` can outrank a
+    /// contract; `Pass`, `Double`, and `Redouble` never do.
+    pub fn outranks(&self, current: Option<Contract>) -> bool {
+        match self {
+            Call::Bid(level, strain) => {
+                let bid = Contract::from_bid(*level, *strain);
+                match current {
+                    None => true,
+                    Some(current) => bid > current,
+                }
+            }
+            Call::Pass | Call::Double | Call::Redouble => false,
+        }
+    }
+
+    /// Whether this call is legal to make next, given the calls already made
+    /// this auction (oldest first). Players strictly alternate sides on every
+    /// turn, so the side that made the call `n` positions back is the same
+    /// side as the caller now iff `n` is even.
+    pub fn is_legal_after(&self, auction_so_far: &[Call]) -> bool {
+        let last_bid = auction_so_far.iter().rev().find_map(|call| match call {
+            Call::Bid(level, strain) => Some(Contract::from_bid(*level, *strain)),
+            _ => None,
+        });
+        let last_non_pass = auction_so_far
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, call)| **call != Call::Pass);
+
+        match self {
+            Call::Pass => true,
+            Call::Bid(level, _) => (1..=7).contains(level) && self.outranks(last_bid),
+            Call::Double => match last_non_pass {
+                Some((index, Call::Bid(_, _))) => {
+                    (auction_so_far.len() - index) % 2 == 1
+                }
+                _ => false,
+            },
+            Call::Redouble => match last_non_pass {
+                Some((index, Call::Double)) => {
+                    (auction_so_far.len() - index) % 2 == 1
+                }
+                _ => false,
+            },
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
-pub struct Card(pub u32, pub Suit);
+pub struct Card(pub Rank, pub Suit);
+
+/// A card string was not a suit letter followed by a valid rank, e.g. `SA` or `HT`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ParseCardError;
+
+impl std::fmt::Display for ParseCardError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid card")
+    }
+}
+
+impl std::error::Error for ParseCardError {}
+
+impl std::fmt::Display for Card {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Card(rank, suit) = self;
+        write!(
+            f,
+            "{}{}",
+            match suit {
+                Suit::Spades => "S",
+                Suit::Hearts => "H",
+                Suit::Diamonds => "D",
+                Suit::Clubs => "C",
+            },
+            rank
+        )
+    }
+}
+
+impl std::str::FromStr for Card {
+    type Err = ParseCardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        let suit_char = chars.next().ok_or(ParseCardError)?;
+        let rank_char = chars.next().ok_or(ParseCardError)?;
+        if chars.next().is_some() {
+            return Err(ParseCardError);
+        }
+        let suit = match suit_char {
+            'S' | 's' => Suit::Spades,
+            'H' | 'h' => Suit::Hearts,
+            'D' | 'd' => Suit::Diamonds,
+            'C' | 'c' => Suit::Clubs,
+            _ => return Err(ParseCardError),
+        };
+        let rank = Rank::from_char(rank_char).map_err(|_| ParseCardError)?;
+        Ok(Card(rank, suit))
+    }
+}
+
+const STRAINS: [Strain; 5] = [
+    Strain::NoTrump,
+    Strain::Suit(Suit::Spades),
+    Strain::Suit(Suit::Hearts),
+    Strain::Suit(Suit::Diamonds),
+    Strain::Suit(Suit::Clubs),
+];
+
+/// The sequence of calls made in a bidding auction, starting with `dealer`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Auction {
+    dealer: Seat,
+    calls: Vec<Call>,
+}
+
+impl Auction {
+    pub fn new(dealer: Seat) -> Self {
+        Auction {
+            dealer,
+            calls: Vec::new(),
+        }
+    }
+
+    pub fn dealer(&self) -> Seat {
+        self.dealer
+    }
+
+    pub fn calls(&self) -> &[Call] {
+        &self.calls
+    }
+
+    /// The seat whose turn it is to call next.
+    pub fn to_call(&self) -> Seat {
+        self.seat_at(self.calls.len())
+    }
+
+    pub fn is_legal(&self, call: Call) -> bool {
+        !self.is_complete() && call.is_legal_after(&self.calls)
+    }
+
+    /// All calls that would be legal to make right now.
+    pub fn legal_calls(&self) -> Vec<Call> {
+        if self.is_complete() {
+            return Vec::new();
+        }
+        let mut calls = vec![Call::Pass, Call::Double, Call::Redouble];
+        for level in 1..=7 {
+            for strain in STRAINS {
+                calls.push(Call::Bid(level, strain));
+            }
+        }
+        calls
+            .into_iter()
+            .filter(|call| call.is_legal_after(&self.calls))
+            .collect()
+    }
+
+    /// Appends `call` if it's legal, returning whether it was accepted.
+    pub fn make_call(&mut self, call: Call) -> bool {
+        if self.is_legal(call) {
+            self.calls.push(call);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// The auction is over once there have been three passes following a bid,
+    /// double, or redouble, or four passes in a row if the board is passed out.
+    pub fn is_complete(&self) -> bool {
+        let n = self.calls.len();
+        n >= 4 && self.calls[n - 3..].iter().all(|call| *call == Call::Pass)
+    }
+
+    /// Resolves the final contract and declarer, or `None` if the auction
+    /// isn't finished or was passed out. The declarer is the first player on
+    /// the declaring side, in auction order, to have named the final strain —
+    /// not necessarily whoever made the final bid.
+    pub fn resolve(&self) -> Option<(Contract, Seat)> {
+        if !self.is_complete() {
+            return None;
+        }
+
+        let last_bid_index = self
+            .calls
+            .iter()
+            .rposition(|call| matches!(call, Call::Bid(_, _)))?;
+        let (level, strain) = match self.calls[last_bid_index] {
+            Call::Bid(level, strain) => (level, strain),
+            _ => unreachable!(),
+        };
+        let declaring_side = self.seat_at(last_bid_index).side();
+
+        let declarer = self.calls.iter().enumerate().find_map(|(index, call)| {
+            match call {
+                Call::Bid(_, bid_strain) if *bid_strain == strain => {
+                    let seat = self.seat_at(index);
+                    if seat.side() == declaring_side {
+                        Some(seat)
+                    } else {
+                        None
+                    }
+                }
+                _ => None,
+            }
+        })?;
+
+        let mut doubling = Doubling::Undoubled;
+        for call in &self.calls[last_bid_index + 1..] {
+            match call {
+                Call::Double => doubling = Doubling::Doubled,
+                Call::Redouble => doubling = Doubling::Redoubled,
+                _ => {}
+            }
+        }
+
+        Some((
+            Contract {
+                level,
+                strain,
+                doubling,
+            },
+            declarer,
+        ))
+    }
+
+    fn seat_at(&self, index: usize) -> Seat {
+        let mut seat = self.dealer;
+        for _ in 0..index {
+            seat = seat.next();
+        }
+        seat
+    }
+}
+
+/// Per-level trick value for `strain`: `(first trick, each subsequent trick)`.
+fn trick_values(strain: Strain) -> (i32, i32) {
+    match strain {
+        Strain::NoTrump => (40, 30),
+        Strain::Suit(Suit::Spades) | Strain::Suit(Suit::Hearts) => (30, 30),
+        Strain::Suit(Suit::Diamonds) | Strain::Suit(Suit::Clubs) => (20, 20),
+    }
+}
+
+fn made_score(contract: Contract, overtricks: u8, vulnerable: bool) -> i32 {
+    let (first_trick, per_trick) = trick_values(contract.strain);
+    let multiplier = match contract.doubling {
+        Doubling::Undoubled => 1,
+        Doubling::Doubled => 2,
+        Doubling::Redoubled => 4,
+    };
+    let trick_points = (first_trick + per_trick * (contract.level as i32 - 1)) * multiplier;
+
+    let mut total = trick_points;
+    total += if trick_points >= 100 {
+        if vulnerable {
+            500
+        } else {
+            300
+        }
+    } else {
+        50
+    };
+
+    total += match contract.level {
+        6 => {
+            if vulnerable {
+                750
+            } else {
+                500
+            }
+        }
+        7 => {
+            if vulnerable {
+                1500
+            } else {
+                1000
+            }
+        }
+        _ => 0,
+    };
+
+    total += match contract.doubling {
+        Doubling::Undoubled => 0,
+        Doubling::Doubled => 50,
+        Doubling::Redoubled => 100,
+    };
+
+    let overtrick_value = match contract.doubling {
+        Doubling::Undoubled => per_trick,
+        Doubling::Doubled => {
+            if vulnerable {
+                200
+            } else {
+                100
+            }
+        }
+        Doubling::Redoubled => {
+            if vulnerable {
+                400
+            } else {
+                200
+            }
+        }
+    };
+    total += overtrick_value * overtricks as i32;
+
+    total
+}
+
+fn undertrick_penalty(doubling: Doubling, undertricks: u8, vulnerable: bool) -> i32 {
+    match doubling {
+        Doubling::Undoubled => {
+            let per_trick = if vulnerable { 100 } else { 50 };
+            per_trick * undertricks as i32
+        }
+        Doubling::Doubled | Doubling::Redoubled => {
+            let mut total = 0;
+            for trick in 1..=undertricks {
+                total += if vulnerable {
+                    if trick == 1 {
+                        200
+                    } else {
+                        300
+                    }
+                } else {
+                    match trick {
+                        1 => 100,
+                        2 | 3 => 200,
+                        _ => 300,
+                    }
+                };
+            }
+            if doubling == Doubling::Redoubled {
+                total *= 2;
+            }
+            total
+        }
+    }
+}
+
+/// Signed duplicate score for the declaring side, given the final `contract`,
+/// who declared it, how many tricks declarer's side took (0..=13), and which
+/// sides are vulnerable.
+pub fn score(
+    contract: Contract,
+    declarer: Seat,
+    tricks_taken: u8,
+    vulnerable: PerSide<bool>,
+) -> i32 {
+    let is_vulnerable = vulnerable[declarer.side()];
+    let tricks_needed = 6 + contract.level;
+
+    if tricks_taken >= tricks_needed {
+        made_score(contract, tricks_taken - tricks_needed, is_vulnerable)
+    } else {
+        -undertrick_penalty(contract.doubling, tricks_needed - tricks_taken, is_vulnerable)
+    }
+}
+
+/// One player's thirteen cards, one `Holding` per suit.
+pub type Hand = PerSuit<Holding>;
+
+/// All four hands of a deal, indexed by `Seat`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Deal(pub PerSeat<Hand>);
+
+/// A deal string didn't parse: bad syntax, an invalid rank, a hand that
+/// didn't hold thirteen cards, or a card dealt more than once.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseDealError {
+    InvalidFormat,
+    InvalidRank(ParseRankError),
+    WrongHandSize(Seat, u32),
+    DuplicateCard(Card),
+}
+
+impl std::fmt::Display for ParseDealError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseDealError::InvalidFormat => write!(f, "invalid deal format"),
+            ParseDealError::InvalidRank(e) => write!(f, "{}", e),
+            ParseDealError::WrongHandSize(seat, count) => {
+                write!(f, "{:?} holds {} cards, expected 13", seat, count)
+            }
+            ParseDealError::DuplicateCard(card) => write!(f, "{} appears more than once", card),
+        }
+    }
+}
+
+impl std::error::Error for ParseDealError {}
+
+impl From<ParseRankError> for ParseDealError {
+    fn from(e: ParseRankError) -> Self {
+        ParseDealError::InvalidRank(e)
+    }
+}
+
+fn hand_to_string(hand: &Hand) -> String {
+    format!(
+        "{}.{}.{}.{}",
+        hand.spades, hand.hearts, hand.diamonds, hand.clubs
+    )
+}
+
+impl std::fmt::Display for Deal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "N:{} {} {} {}",
+            hand_to_string(&self.0.north),
+            hand_to_string(&self.0.east),
+            hand_to_string(&self.0.south),
+            hand_to_string(&self.0.west),
+        )
+    }
+}
+
+impl std::str::FromStr for Deal {
+    type Err = ParseDealError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (seat_str, hands_str) = s.split_once(':').ok_or(ParseDealError::InvalidFormat)?;
+        let first_seat = match seat_str {
+            "N" => Seat::North,
+            "E" => Seat::East,
+            "S" => Seat::South,
+            "W" => Seat::West,
+            _ => return Err(ParseDealError::InvalidFormat),
+        };
+
+        let hand_tokens: Vec<&str> = hands_str.split_whitespace().collect();
+        if hand_tokens.len() != 4 {
+            return Err(ParseDealError::InvalidFormat);
+        }
+
+        let mut hands = PerSeat::new(PerSuit::new(Holding::new()));
+        let mut seen: PerSuit<Holding> = PerSuit::new(Holding::new());
+        let mut seat = first_seat;
+        for token in hand_tokens {
+            let suit_strs: Vec<&str> = token.split('.').collect();
+            if suit_strs.len() != 4 {
+                return Err(ParseDealError::InvalidFormat);
+            }
+            let hand = Hand {
+                spades: suit_strs[0].parse::<Holding>()?,
+                hearts: suit_strs[1].parse::<Holding>()?,
+                diamonds: suit_strs[2].parse::<Holding>()?,
+                clubs: suit_strs[3].parse::<Holding>()?,
+            };
+
+            let count = hand.map(|holding| holding.count()).sum();
+            if count != 13 {
+                return Err(ParseDealError::WrongHandSize(seat, count));
+            }
+
+            for suit in [Suit::Spades, Suit::Hearts, Suit::Diamonds, Suit::Clubs] {
+                let overlap = Holding(seen[suit].0 & hand[suit].0);
+                if let Some(rank) = overlap.iter().next() {
+                    return Err(ParseDealError::DuplicateCard(Card(rank, suit)));
+                }
+                seen[suit].0 |= hand[suit].0;
+            }
+
+            hands[seat] = hand;
+            seat = seat.next();
+        }
+
+        Ok(Deal(hands))
+    }
+}
+
+impl serde::Serialize for Deal {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Deal {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+fn honor_points(holding: Holding) -> u32 {
+    let mut points = 0;
+    if holding.contains(Rank::new(14).unwrap()) {
+        points += 4;
+    }
+    if holding.contains(Rank::new(13).unwrap()) {
+        points += 3;
+    }
+    if holding.contains(Rank::new(12).unwrap()) {
+        points += 2;
+    }
+    if holding.contains(Rank::new(11).unwrap()) {
+        points += 1;
+    }
+    points
+}
+
+impl Hand {
+    /// High card points: A=4, K=3, Q=2, J=1.
+    pub fn hcp(&self) -> u32 {
+        self.iter().map(|holding| honor_points(*holding)).sum()
+    }
+
+    /// Number of cards held in each suit.
+    pub fn shape(&self) -> PerSuit<u8> {
+        self.map(|holding| holding.count() as u8)
+    }
+}
+
+/// Per-seat requirements a generated hand must satisfy.
+#[derive(Clone, Debug)]
+pub struct SeatConstraints {
+    pub shape: PerSuit<Range<u8>>,
+    pub hcp: Range<u8>,
+    pub fixed_cards: Vec<Card>,
+    pub forbidden_cards: Vec<Card>,
+}
+
+impl Default for SeatConstraints {
+    fn default() -> Self {
+        SeatConstraints {
+            shape: PerSuit::new_with(|| 0..14),
+            hcp: 0..38,
+            fixed_cards: Vec::new(),
+            forbidden_cards: Vec::new(),
+        }
+    }
+}
+
+impl SeatConstraints {
+    fn is_satisfied_by(&self, hand: &Hand) -> bool {
+        if !self.hcp.contains(&(hand.hcp() as u8)) {
+            return false;
+        }
+        let shape = hand.shape();
+        if !self.shape.spades.contains(&shape.spades)
+            || !self.shape.hearts.contains(&shape.hearts)
+            || !self.shape.diamonds.contains(&shape.diamonds)
+            || !self.shape.clubs.contains(&shape.clubs)
+        {
+            return false;
+        }
+        if self
+            .fixed_cards
+            .iter()
+            .any(|card| !hand[card.1].contains(card.0))
+        {
+            return false;
+        }
+        if self
+            .forbidden_cards
+            .iter()
+            .any(|card| hand[card.1].contains(card.0))
+        {
+            return false;
+        }
+        true
+    }
+}
+
+/// Constraints on a randomly generated `Deal`, built up one seat at a time.
+#[derive(Clone, Debug)]
+pub struct DealConstraints {
+    pub seats: PerSeat<SeatConstraints>,
+    pub max_attempts: u32,
+}
+
+impl Default for DealConstraints {
+    fn default() -> Self {
+        DealConstraints {
+            seats: PerSeat::new_with(SeatConstraints::default),
+            max_attempts: 10_000,
+        }
+    }
+}
+
+impl DealConstraints {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_shape(mut self, seat: Seat, shape: PerSuit<Range<u8>>) -> Self {
+        self.seats[seat].shape = shape;
+        self
+    }
+
+    pub fn with_hcp(mut self, seat: Seat, hcp: Range<u8>) -> Self {
+        self.seats[seat].hcp = hcp;
+        self
+    }
+
+    pub fn with_fixed_card(mut self, seat: Seat, card: Card) -> Self {
+        self.seats[seat].fixed_cards.push(card);
+        self
+    }
+
+    pub fn with_forbidden_card(mut self, seat: Seat, card: Card) -> Self {
+        self.seats[seat].forbidden_cards.push(card);
+        self
+    }
+
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+}
+
+fn all_cards() -> Vec<Card> {
+    let mut cards = Vec::with_capacity(52);
+    for suit in [Suit::Spades, Suit::Hearts, Suit::Diamonds, Suit::Clubs] {
+        for value in 2..=14 {
+            cards.push(Card(Rank::new(value).unwrap(), suit));
+        }
+    }
+    cards
+}
+
+fn hand_from_cards(cards: &[Card]) -> Hand {
+    let mut hand = Hand {
+        spades: Holding::new(),
+        hearts: Holding::new(),
+        diamonds: Holding::new(),
+        clubs: Holding::new(),
+    };
+    for Card(rank, suit) in cards {
+        hand[*suit].add(*rank);
+    }
+    hand
+}
+
+/// Shuffles a fresh 52-card deck into a `Deal` satisfying `constraints`,
+/// retrying up to `constraints.max_attempts` times before giving up.
+pub fn deal<R: Rng + ?Sized>(rng: &mut R, constraints: &DealConstraints) -> Option<Deal> {
+    let mut cards = all_cards();
+    for _ in 0..constraints.max_attempts {
+        cards.shuffle(rng);
+        let hands = PerSeat {
+            north: hand_from_cards(&cards[0..13]),
+            east: hand_from_cards(&cards[13..26]),
+            south: hand_from_cards(&cards[26..39]),
+            west: hand_from_cards(&cards[39..52]),
+        };
+
+        if constraints.seats.north.is_satisfied_by(&hands.north)
+            && constraints.seats.east.is_satisfied_by(&hands.east)
+            && constraints.seats.south.is_satisfied_by(&hands.south)
+            && constraints.seats.west.is_satisfied_by(&hands.west)
+        {
+            return Some(Deal(hands));
+        }
+    }
+    None
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::SeedableRng;
 
     #[test]
     fn test_holding_iterator() {
         let holding = Holding(0x124);
-        let held_cards: Vec<u32> = holding.iter().collect();
-        assert_eq!(held_cards, vec![2, 5, 8]);
+        let held_cards: Vec<Rank> = holding.iter().collect();
+        assert_eq!(
+            held_cards,
+            vec![Rank::new(2).unwrap(), Rank::new(5).unwrap(), Rank::new(8).unwrap()]
+        );
+    }
+
+    #[test]
+    fn test_rank_label_roundtrip() {
+        for value in 2..=14 {
+            let rank = Rank::new(value).unwrap();
+            assert_eq!(rank.label().to_string().parse::<Rank>().unwrap(), rank);
+        }
+    }
+
+    #[test]
+    fn test_holding_display_roundtrip() {
+        let holding: Holding = "AKQ972".parse().unwrap();
+        assert_eq!(holding.to_string(), "AKQ972");
+    }
+
+    #[test]
+    fn test_card_display_roundtrip() {
+        let card: Card = "HT".parse().unwrap();
+        assert_eq!(card.to_string(), "HT");
+    }
+
+    #[test]
+    fn test_contract_ord() {
+        let one_club = Contract::from_bid(1, Strain::Suit(Suit::Clubs));
+        let one_notrump = Contract::from_bid(1, Strain::NoTrump);
+        let two_clubs = Contract::from_bid(2, Strain::Suit(Suit::Clubs));
+        assert!(one_club < one_notrump);
+        assert!(one_notrump < two_clubs);
+    }
+
+    #[test]
+    fn test_call_is_legal_after() {
+        let auction = vec![Call::Pass, Call::Bid(1, Strain::Suit(Suit::Spades))];
+        assert!(Call::Double.is_legal_after(&auction));
+        assert!(!Call::Redouble.is_legal_after(&auction));
+        assert!(!Call::Bid(1, Strain::Suit(Suit::Spades)).is_legal_after(&auction));
+        assert!(Call::Bid(1, Strain::NoTrump).is_legal_after(&auction));
+        assert!(!Call::Bid(0, Strain::NoTrump).is_legal_after(&[]));
+        assert!(!Call::Bid(8, Strain::NoTrump).is_legal_after(&[]));
+
+        let doubled = vec![
+            Call::Pass,
+            Call::Bid(1, Strain::Suit(Suit::Spades)),
+            Call::Double,
+        ];
+        // The bidder's partner may redouble immediately after the opponent's double.
+        assert!(Call::Redouble.is_legal_after(&doubled));
+        assert!(!Call::Double.is_legal_after(&doubled));
+
+        let doubled_in_pass_out_seat = vec![
+            Call::Bid(1, Strain::Suit(Suit::Spades)),
+            Call::Pass,
+            Call::Pass,
+            Call::Double,
+        ];
+        assert!(Call::Redouble.is_legal_after(&doubled_in_pass_out_seat));
+    }
+
+    #[test]
+    fn test_auction_passed_out() {
+        let mut auction = Auction::new(Seat::North);
+        for _ in 0..4 {
+            assert!(auction.make_call(Call::Pass));
+        }
+        assert!(auction.is_complete());
+        assert_eq!(auction.resolve(), None);
+    }
+
+    #[test]
+    fn test_auction_declarer_is_first_to_name_strain() {
+        // North opens 1S, East overcalls 1NT, South raises to 2S, then West,
+        // North and East pass. North named spades first for NS, so North
+        // declares even though South made the final bid.
+        let mut auction = Auction::new(Seat::North);
+        for call in [
+            Call::Bid(1, Strain::Suit(Suit::Spades)),
+            Call::Bid(1, Strain::NoTrump),
+            Call::Bid(2, Strain::Suit(Suit::Spades)),
+            Call::Pass,
+            Call::Pass,
+            Call::Pass,
+        ] {
+            assert!(auction.make_call(call));
+        }
+        assert!(auction.is_complete());
+        let (contract, declarer) = auction.resolve().unwrap();
+        assert_eq!(contract.level, 2);
+        assert_eq!(contract.strain, Strain::Suit(Suit::Spades));
+        assert_eq!(contract.doubling, Doubling::Undoubled);
+        assert_eq!(declarer, Seat::North);
+    }
+
+    #[test]
+    fn test_auction_doubling() {
+        let mut auction = Auction::new(Seat::North);
+        for call in [
+            Call::Bid(3, Strain::Suit(Suit::Hearts)),
+            Call::Double,
+            Call::Redouble,
+            Call::Pass,
+            Call::Pass,
+            Call::Pass,
+        ] {
+            assert!(auction.make_call(call));
+        }
+        let (contract, _) = auction.resolve().unwrap();
+        assert_eq!(contract.doubling, Doubling::Redoubled);
+    }
+
+    #[test]
+    fn test_score_game_bonuses() {
+        let not_vulnerable = PerSide::new(false);
+        let vulnerable = PerSide::new(true);
+
+        let four_spades = Contract::from_bid(4, Strain::Suit(Suit::Spades));
+        assert_eq!(score(four_spades, Seat::North, 10, not_vulnerable), 420);
+
+        let three_notrump = Contract::from_bid(3, Strain::NoTrump);
+        assert_eq!(score(three_notrump, Seat::North, 9, not_vulnerable), 400);
+
+        let six_notrump = Contract::from_bid(6, Strain::NoTrump);
+        assert_eq!(score(six_notrump, Seat::North, 12, vulnerable), 1440);
+
+        let seven_notrump = Contract::from_bid(7, Strain::NoTrump);
+        assert_eq!(score(seven_notrump, Seat::North, 13, vulnerable), 2220);
+    }
+
+    #[test]
+    fn test_score_doubled_made() {
+        let not_vulnerable = PerSide::new(false);
+        let contract = Contract {
+            level: 2,
+            strain: Strain::Suit(Suit::Spades),
+            doubling: Doubling::Doubled,
+        };
+        assert_eq!(score(contract, Seat::North, 8, not_vulnerable), 470);
+    }
+
+    #[test]
+    fn test_score_redoubled_made() {
+        let not_vulnerable = PerSide::new(false);
+        let contract = Contract {
+            level: 1,
+            strain: Strain::NoTrump,
+            doubling: Doubling::Redoubled,
+        };
+        assert_eq!(score(contract, Seat::North, 7, not_vulnerable), 560);
+    }
+
+    #[test]
+    fn test_score_undertricks() {
+        let not_vulnerable = PerSide::new(false);
+        let vulnerable = PerSide::new(true);
+        let undoubled = Contract::from_bid(3, Strain::NoTrump);
+
+        assert_eq!(score(undoubled, Seat::North, 8, not_vulnerable), -50);
+        assert_eq!(score(undoubled, Seat::North, 6, vulnerable), -300);
+
+        let doubled = Contract {
+            doubling: Doubling::Doubled,
+            ..undoubled
+        };
+        assert_eq!(score(doubled, Seat::North, 7, not_vulnerable), -300);
+
+        let redoubled = Contract {
+            doubling: Doubling::Redoubled,
+            ..undoubled
+        };
+        assert_eq!(score(redoubled, Seat::North, 5, vulnerable), -2200);
+    }
+
+    fn full_suit() -> Holding {
+        (2..=14).map(|v| Rank::new(v).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_deal_display_roundtrip() {
+        let empty = Hand {
+            spades: Holding::new(),
+            hearts: Holding::new(),
+            diamonds: Holding::new(),
+            clubs: Holding::new(),
+        };
+        let deal = Deal(PerSeat {
+            north: Hand {
+                spades: full_suit(),
+                ..empty
+            },
+            east: Hand {
+                hearts: full_suit(),
+                ..empty
+            },
+            south: Hand {
+                diamonds: full_suit(),
+                ..empty
+            },
+            west: Hand {
+                clubs: full_suit(),
+                ..empty
+            },
+        });
+
+        let s = deal.to_string();
+        assert_eq!(
+            s,
+            "N:AKQJT98765432... .AKQJT98765432.. ..AKQJT98765432. ...AKQJT98765432"
+        );
+        let parsed: Deal = s.parse().unwrap();
+        assert_eq!(parsed, deal);
+        assert_eq!(parsed.to_string(), s);
+    }
+
+    #[test]
+    fn test_deal_rejects_wrong_hand_size() {
+        let empty = Hand {
+            spades: Holding::new(),
+            hearts: Holding::new(),
+            diamonds: Holding::new(),
+            clubs: Holding::new(),
+        };
+        let mut short = full_suit();
+        short.remove(Rank::new(2).unwrap());
+        let short_hand = Hand {
+            spades: short,
+            ..empty
+        };
+
+        let s = format!(
+            "N:{} {} {} {}",
+            hand_to_string(&short_hand),
+            hand_to_string(&empty),
+            hand_to_string(&empty),
+            hand_to_string(&empty),
+        );
+        assert_eq!(
+            s.parse::<Deal>(),
+            Err(ParseDealError::WrongHandSize(Seat::North, 12))
+        );
+    }
+
+    #[test]
+    fn test_deal_rejects_duplicate_card() {
+        let empty = Hand {
+            spades: Holding::new(),
+            hearts: Holding::new(),
+            diamonds: Holding::new(),
+            clubs: Holding::new(),
+        };
+        let spade_only = Hand {
+            spades: full_suit(),
+            ..empty
+        };
+        let heart_only = Hand {
+            hearts: full_suit(),
+            ..empty
+        };
+
+        let s = format!(
+            "N:{} {} {} {}",
+            hand_to_string(&spade_only),
+            hand_to_string(&heart_only),
+            hand_to_string(&spade_only),
+            hand_to_string(&empty),
+        );
+        let two_of_spades = Card(Rank::new(2).unwrap(), Suit::Spades);
+        assert_eq!(
+            s.parse::<Deal>(),
+            Err(ParseDealError::DuplicateCard(two_of_spades))
+        );
+    }
+
+    #[test]
+    fn test_hand_hcp_and_shape() {
+        let hand = Hand {
+            spades: "AKQ2".parse().unwrap(),
+            hearts: "JT92".parse().unwrap(),
+            diamonds: "543".parse().unwrap(),
+            clubs: "KQ876".parse().unwrap(),
+        };
+        assert_eq!(hand.hcp(), 9 + 1 + 3 + 2);
+        assert_eq!(
+            hand.shape(),
+            PerSuit {
+                spades: 4,
+                hearts: 4,
+                diamonds: 3,
+                clubs: 5,
+            }
+        );
+    }
+
+    #[test]
+    fn test_deal_generator_respects_constraints() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+        let constraints = DealConstraints::new()
+            .with_hcp(Seat::North, 15..18)
+            .with_shape(
+                Seat::North,
+                PerSuit {
+                    spades: 5..14,
+                    hearts: 0..14,
+                    diamonds: 0..14,
+                    clubs: 0..14,
+                },
+            )
+            .with_fixed_card(Seat::North, "SA".parse().unwrap())
+            .with_forbidden_card(Seat::South, "SA".parse().unwrap());
+
+        let deal = deal(&mut rng, &constraints).expect("should find a matching deal");
+        let north = deal.0.north;
+        assert!((15..18).contains(&(north.hcp() as u8)));
+        assert!(north.shape().spades >= 5);
+        assert!(north.spades.contains(Rank::new(14).unwrap()));
+        assert!(!deal.0.south.spades.contains(Rank::new(14).unwrap()));
+    }
+
+    #[test]
+    fn test_deal_generator_gives_up() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        // No 13-card hand can have 40 HCP, so this must exhaust its attempts.
+        let constraints = DealConstraints::new()
+            .with_hcp(Seat::North, 40..41)
+            .with_max_attempts(50);
+        assert_eq!(deal(&mut rng, &constraints), None);
     }
 }